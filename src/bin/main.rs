@@ -14,7 +14,7 @@ use embedded_hal_bus::i2c::RefCellDevice;
 
 use embedded_graphics::{
     mono_font::{ascii::FONT_10X20, MonoTextStyle},
-    primitives::Rectangle,
+    primitives::{PrimitiveStyle, Rectangle},
     text::Text,
 };
 use embedded_graphics::{pixelcolor::Rgb565, prelude::*};
@@ -37,7 +37,11 @@ use mipidsi::models::ST7789;
 use mipidsi::options::{ColorInversion, Orientation, Rotation};
 use static_cell::StaticCell;
 
+use hello_display::dirty_rect::DirtyTracker;
+use hello_display::orientation::{estimate_gyro_bias, ComplementaryFilter};
 use hello_display::qmi8658a::Qmi8658a;
+use hello_display::smoothing::Smoothed;
+use hello_display::touch::{Gesture, TouchPoint, TouchTracker};
 
 #[panic_handler]
 fn panic(panic_info: &core::panic::PanicInfo) -> ! {
@@ -63,6 +67,24 @@ const DISPLAY_SIZE_W: u16 = 320; // X-axis
 
 static SPI_BUFFER: StaticCell<[u8; 4096]> = StaticCell::new();
 
+/// Whether `a` and `b` share any pixels.
+fn rectangles_overlap(a: Rectangle, b: Rectangle) -> bool {
+    let overlap = a.intersection(&b);
+    overlap.size.width > 0 && overlap.size.height > 0
+}
+
+/// Extracts the pixels of `rect` out of a row-major `DISPLAY_SIZE_W`-wide
+/// framebuffer, in the order `fill_contiguous` expects.
+fn pixels_in_rect(data: &[Rgb565], rect: Rectangle) -> impl Iterator<Item = Rgb565> + '_ {
+    let stride = DISPLAY_SIZE_W as i32;
+    let Point { x: x0, y: y0 } = rect.top_left;
+    let width = rect.size.width as usize;
+    (0..rect.size.height as i32).flat_map(move |row| {
+        let start = ((y0 + row) * stride + x0) as usize;
+        data[start..start + width].iter().copied()
+    })
+}
+
 #[main]
 fn main() -> ! {
     esp_println::logger::init_logger_from_env();
@@ -172,22 +194,84 @@ fn main() -> ! {
     let mut frame_buffer =
         FrameBuf::new(&mut data, DISPLAY_SIZE_W as usize, DISPLAY_SIZE_H as usize);
 
+    frame_buffer.clear(Rgb565::WHITE).ok();
+
     let character_style = MonoTextStyle::new(&FONT_10X20, Rgb565::BLACK);
     let mut text = Text::new("Hello, World!", Point::new(90, 0), character_style);
     let mut y = 0;
 
+    // On-screen button: tapping it resets the scrolling text to the top.
+    // The scrolling text passes over the button every cycle, so the button
+    // is drawn on the first frame and redrawn on top of the text on every
+    // frame where the text overlapped it.
+    let reset_button = Rectangle::new(
+        Point::new(0, DISPLAY_SIZE_H as i32 - 30),
+        Size::new(80, 30),
+    );
+    let mut touch_tracker = TouchTracker::new();
+    let mut dirty = DirtyTracker::new();
+    let mut previous_text_bounds: Option<(Rectangle, Rectangle)> = None;
+
     imu.initialize().expect("failed to initialize IMU");
     match imu.read_chip_id() {
         Ok(id) => info!("IMU ID: {id}"),
         Err(err) => error!("Error reading chip id: {err}"),
     }
 
+    // Board must be held still during boot for this to be accurate.
+    let gyro_bias =
+        estimate_gyro_bias(|| imu.read_gyro_dps(), 100).unwrap_or([0.0, 0.0, 0.0]);
+    let mut orientation_filter = ComplementaryFilter::new(gyro_bias);
+    let mut last_orientation_update = Instant::now();
+    let mut smoothed_temperature = Smoothed::new_default();
+
+    let full_area = Rectangle::new(Point::zero(), frame_buffer.size());
+
     loop {
         if let Ok(temperature) = imu.read_temperature() {
-            info!("Temperature: {temperature:#06X} {}", temperature as f32 / 256f32);
+            let smoothed = smoothed_temperature.update(temperature);
+            info!("Temperature: {smoothed:.2} C");
         }
 
-        frame_buffer.clear(Rgb565::WHITE).ok();
+        if let (Ok(accel_g), Ok(gyro_dps)) = (imu.read_accel_g(), imu.read_gyro_dps()) {
+            let now = Instant::now();
+            let dt_seconds = now.duration_since_epoch().as_micros().wrapping_sub(
+                last_orientation_update.duration_since_epoch().as_micros(),
+            ) as f32
+                / 1_000_000.0;
+            last_orientation_update = now;
+
+            let (pitch_deg, roll_deg) = orientation_filter.update(accel_g, gyro_dps, dt_seconds);
+            info!("Pitch: {pitch_deg:.1} deg, Roll: {roll_deg:.1} deg");
+        }
+
+        let now_ms = Instant::now().duration_since_epoch().as_millis();
+        let touch_point = touch_driver
+            .read_touch_point()
+            .ok()
+            .flatten()
+            .map(|(x, y)| TouchPoint { x, y });
+        if let Some(event) = touch_tracker.update(touch_point, now_ms) {
+            if let Some(Gesture::Tap(point)) = touch_tracker.recognize(event, now_ms) {
+                if point.hits(reset_button) {
+                    y = 0;
+                }
+            }
+        }
+
+        // Erase only the area the scrolling text occupied last frame.
+        // Nothing has drawn the button yet on the first frame.
+        let mut button_needs_redraw = previous_text_bounds.is_none();
+        if let Some((prev_text2, prev_text)) = previous_text_bounds {
+            for bounds in [prev_text2, prev_text] {
+                bounds
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::WHITE))
+                    .draw(&mut frame_buffer)
+                    .ok();
+                dirty.mark(bounds);
+                button_needs_redraw |= rectangles_overlap(bounds, reset_button);
+            }
+        }
 
         let message = format!(
             "Current timestamp: {} ms",
@@ -205,11 +289,36 @@ fn main() -> ! {
             y += 1;
         }
 
-        // let start = Instant::now();
-        let area = Rectangle::new(Point::zero(), frame_buffer.size());
-        display
-            .fill_contiguous(&area, frame_buffer.data.iter().copied())
+        dirty.mark(text2.bounding_box());
+        dirty.mark(text.bounding_box());
+        button_needs_redraw |= rectangles_overlap(text2.bounding_box(), reset_button);
+        button_needs_redraw |= rectangles_overlap(text.bounding_box(), reset_button);
+        previous_text_bounds = Some((text2.bounding_box(), text.bounding_box()));
+
+        // Apart from the first frame, only the frames where the scrolling
+        // text actually overlapped the button need to restore it; otherwise leave its pixels (and
+        // the SPI bandwidth to retransmit them) untouched.
+        if button_needs_redraw {
+            reset_button
+                .into_styled(PrimitiveStyle::with_stroke(Rgb565::BLACK, 1))
+                .draw(&mut frame_buffer)
+                .ok();
+            Text::new(
+                "Reset",
+                Point::new(10, DISPLAY_SIZE_H as i32 - 10),
+                character_style,
+            )
+            .draw(&mut frame_buffer)
             .ok();
+            dirty.mark(reset_button);
+        }
+
+        // let start = Instant::now();
+        if let Some(dirty_area) = dirty.take(full_area) {
+            display
+                .fill_contiguous(&dirty_area, pixels_in_rect(&frame_buffer.data[..], dirty_area))
+                .ok();
+        }
         // info!("{}", start.elapsed());
     }
 }
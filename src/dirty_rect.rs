@@ -0,0 +1,52 @@
+//! Dirty-rectangle tracking so only the pixels that actually changed get
+//! pushed over SPI, instead of the whole frame every iteration.
+
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+
+/// Accumulates the bounding box of everything drawn (or about to be
+/// overdrawn) between two [`DirtyTracker::take`] calls.
+#[derive(Debug, Default)]
+pub struct DirtyTracker {
+    bounds: Option<Rectangle>,
+}
+
+impl DirtyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Unions `rect` into the accumulated dirty region.
+    pub fn mark(&mut self, rect: Rectangle) {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => union(bounds, rect),
+            None => rect,
+        });
+    }
+
+    /// Returns the accumulated dirty region, clamped to `display_bounds`,
+    /// and resets the tracker for the next frame.
+    pub fn take(&mut self, display_bounds: Rectangle) -> Option<Rectangle> {
+        self.bounds.take().and_then(|rect| clamp(rect, display_bounds))
+    }
+}
+
+fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    let top_left = Point::new(a.top_left.x.min(b.top_left.x), a.top_left.y.min(b.top_left.y));
+    let a_bottom_right = a.top_left + a.size;
+    let b_bottom_right = b.top_left + b.size;
+    let bottom_right = Point::new(
+        a_bottom_right.x.max(b_bottom_right.x),
+        a_bottom_right.y.max(b_bottom_right.y),
+    );
+    Rectangle::new(top_left, (bottom_right - top_left).to_unsigned())
+}
+
+fn clamp(rect: Rectangle, display_bounds: Rectangle) -> Option<Rectangle> {
+    let clamped = rect.intersection(&display_bounds);
+    if clamped.size.width == 0 || clamped.size.height == 0 {
+        None
+    } else {
+        Some(clamped)
+    }
+}
@@ -0,0 +1,7 @@
+#![no_std]
+
+pub mod dirty_rect;
+pub mod orientation;
+pub mod qmi8658a;
+pub mod smoothing;
+pub mod touch;
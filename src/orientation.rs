@@ -0,0 +1,90 @@
+//! Complementary-filter pitch/roll estimation on top of [`crate::qmi8658a`] readings.
+
+use libm::atan2f;
+
+/// Maximum timestep accepted by [`ComplementaryFilter::update`], in seconds.
+///
+/// Bounds the gyro integration step so a stalled loop iteration (e.g. a
+/// blocked I2C read) doesn't inject a huge angle jump once it resumes.
+const MAX_DT_SECONDS: f32 = 0.2;
+
+/// Blend factor between the gyro-integrated angle and the accelerometer
+/// angle. Values closer to 1.0 trust the gyro more and drift less to the
+/// accelerometer's high-frequency noise, at the cost of slower correction
+/// of gyro bias drift.
+const DEFAULT_ALPHA: f32 = 0.98;
+
+/// Fuses accelerometer and gyroscope samples into stable pitch/roll angles
+/// using a complementary filter.
+#[derive(Debug, Clone, Copy)]
+pub struct ComplementaryFilter {
+    alpha: f32,
+    gyro_bias: [f32; 3],
+    pitch_deg: f32,
+    roll_deg: f32,
+}
+
+impl ComplementaryFilter {
+    /// Creates a filter seeded at zero pitch/roll with the given gyro bias
+    /// (see [`estimate_gyro_bias`]) and the default blend factor.
+    pub fn new(gyro_bias: [f32; 3]) -> Self {
+        Self {
+            alpha: DEFAULT_ALPHA,
+            gyro_bias,
+            pitch_deg: 0.0,
+            roll_deg: 0.0,
+        }
+    }
+
+    /// Overrides the default blend factor (`alpha`, typically ~0.98).
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Current pitch angle, in degrees.
+    pub fn pitch_deg(&self) -> f32 {
+        self.pitch_deg
+    }
+
+    /// Current roll angle, in degrees.
+    pub fn roll_deg(&self) -> f32 {
+        self.roll_deg
+    }
+
+    /// Updates the filter with a new accel (g) and gyro (dps) sample over
+    /// timestep `dt_seconds`, and returns the updated `(pitch_deg, roll_deg)`.
+    pub fn update(&mut self, accel_g: [f32; 3], gyro_dps: [f32; 3], dt_seconds: f32) -> (f32, f32) {
+        let dt = dt_seconds.clamp(0.0, MAX_DT_SECONDS);
+
+        let [ax, ay, az] = accel_g;
+        let gyro_x = gyro_dps[0] - self.gyro_bias[0];
+        let gyro_y = gyro_dps[1] - self.gyro_bias[1];
+
+        let pitch_acc = atan2f(ax, libm::sqrtf(ay * ay + az * az)).to_degrees();
+        let roll_acc = atan2f(ay, az).to_degrees();
+
+        self.pitch_deg = self.alpha * (self.pitch_deg + gyro_x * dt) + (1.0 - self.alpha) * pitch_acc;
+        self.roll_deg = self.alpha * (self.roll_deg + gyro_y * dt) + (1.0 - self.alpha) * roll_acc;
+
+        (self.pitch_deg, self.roll_deg)
+    }
+}
+
+/// Averages `sample_count` gyro readings to estimate the at-rest bias to
+/// subtract from future readings. Call once during init while the board is
+/// stationary.
+pub fn estimate_gyro_bias<E>(
+    mut read_gyro_dps: impl FnMut() -> Result<[f32; 3], E>,
+    sample_count: u32,
+) -> Result<[f32; 3], E> {
+    let mut sum = [0.0f32; 3];
+    for _ in 0..sample_count {
+        let sample = read_gyro_dps()?;
+        sum[0] += sample[0];
+        sum[1] += sample[1];
+        sum[2] += sample[2];
+    }
+    let n = sample_count.max(1) as f32;
+    Ok([sum[0] / n, sum[1] / n, sum[2] / n])
+}
@@ -1,11 +1,11 @@
-use core::ops::Shl;
-
 use embedded_hal::i2c::I2c;
 
 #[derive(Debug)]
 pub struct Qmi8658a<I2C: I2c> {
     i2c: I2C,
     address: u8,
+    accel_range: AccelRange,
+    gyro_range: GyroRange,
 }
 
 #[derive(Debug)]
@@ -18,17 +18,224 @@ pub struct ImuData {
     pub gyro_z: i16, // Yaw
 }
 
+/// Accelerometer full-scale range, written to the `ACC_RANGE` field of `CTRL2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccelRange {
+    G2,
+    G4,
+    G8,
+    G16,
+}
+
+impl AccelRange {
+    fn bits(self) -> u8 {
+        match self {
+            AccelRange::G2 => 0b000 << 4,
+            AccelRange::G4 => 0b001 << 4,
+            AccelRange::G8 => 0b010 << 4,
+            AccelRange::G16 => 0b011 << 4,
+        }
+    }
+
+    /// LSB per g for this range, used to convert raw counts to physical units.
+    fn sensitivity(self) -> f32 {
+        match self {
+            AccelRange::G2 => 16384.0,
+            AccelRange::G4 => 8192.0,
+            AccelRange::G8 => 4096.0,
+            AccelRange::G16 => 2048.0,
+        }
+    }
+}
+
+/// Gyroscope full-scale range, written to the `GYR_RANGE` field of `CTRL3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GyroRange {
+    Dps16,
+    Dps32,
+    Dps64,
+    Dps128,
+    Dps256,
+    Dps512,
+    Dps1024,
+    Dps2048,
+}
+
+impl GyroRange {
+    fn bits(self) -> u8 {
+        match self {
+            GyroRange::Dps16 => 0b000 << 4,
+            GyroRange::Dps32 => 0b001 << 4,
+            GyroRange::Dps64 => 0b010 << 4,
+            GyroRange::Dps128 => 0b011 << 4,
+            GyroRange::Dps256 => 0b100 << 4,
+            GyroRange::Dps512 => 0b101 << 4,
+            GyroRange::Dps1024 => 0b110 << 4,
+            GyroRange::Dps2048 => 0b111 << 4,
+        }
+    }
+
+    /// LSB per dps for this range, used to convert raw counts to physical units.
+    fn sensitivity(self) -> f32 {
+        match self {
+            GyroRange::Dps16 => 2048.0,
+            GyroRange::Dps32 => 1024.0,
+            GyroRange::Dps64 => 512.0,
+            GyroRange::Dps128 => 256.0,
+            GyroRange::Dps256 => 128.0,
+            GyroRange::Dps512 => 64.0,
+            GyroRange::Dps1024 => 32.0,
+            GyroRange::Dps2048 => 16.0,
+        }
+    }
+}
+
+/// Output data rate, shared by the `ODR` field of `CTRL2` and `CTRL3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputDataRate {
+    Hz8000,
+    Hz4000,
+    Hz2000,
+    Hz1000,
+    Hz500,
+    Hz250,
+    Hz125,
+    Hz62_5,
+    Hz31_25,
+}
+
+impl OutputDataRate {
+    fn bits(self) -> u8 {
+        match self {
+            OutputDataRate::Hz8000 => 0b0000,
+            OutputDataRate::Hz4000 => 0b0001,
+            OutputDataRate::Hz2000 => 0b0010,
+            OutputDataRate::Hz1000 => 0b0011,
+            OutputDataRate::Hz500 => 0b0100,
+            OutputDataRate::Hz250 => 0b0101,
+            OutputDataRate::Hz125 => 0b0110,
+            OutputDataRate::Hz62_5 => 0b0111,
+            OutputDataRate::Hz31_25 => 0b1000,
+        }
+    }
+}
+
+/// Runtime configuration applied by [`Qmi8658a::initialize`].
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub accel_range: AccelRange,
+    pub accel_odr: OutputDataRate,
+    pub gyro_range: GyroRange,
+    pub gyro_odr: OutputDataRate,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            accel_range: AccelRange::G2,
+            accel_odr: OutputDataRate::Hz1000,
+            gyro_range: GyroRange::Dps2048,
+            gyro_odr: OutputDataRate::Hz1000,
+        }
+    }
+}
+
+/// FIFO operating mode, written to the `FIFO_MODE` field of `FIFO_CTRL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FifoMode {
+    /// FIFO disabled; samples are only available through `read_imu_data`.
+    Bypass,
+    /// Stops accepting new samples once full, until drained.
+    Fifo,
+    /// Oldest samples are overwritten once full.
+    Stream,
+}
+
+impl FifoMode {
+    fn bits(self) -> u8 {
+        match self {
+            FifoMode::Bypass => 0b00,
+            FifoMode::Fifo => 0b01,
+            FifoMode::Stream => 0b10,
+        }
+    }
+}
+
+/// Which `INTn` pin an interrupt source is routed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptPin {
+    Int1,
+    Int2,
+}
+
+impl InterruptPin {
+    /// Mask of this pin's enable bit in `CTRL1`.
+    fn enable_bit(self) -> u8 {
+        match self {
+            InterruptPin::Int1 => 1 << 3,
+            InterruptPin::Int2 => 1 << 4,
+        }
+    }
+}
+
+/// Decoded `STATUSINT`/`STATUS1` registers, reporting which motion engine
+/// fired since the last read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InterruptStatus {
+    pub wake_on_motion: bool,
+    pub tap: bool,
+    pub any_motion: bool,
+}
+
 mod registers {
     pub const WHO_AM_I: u8 = 0x00;
     pub const CTRL1: u8 = 0x02;
+    pub const CTRL2: u8 = 0x03;
+    pub const CTRL3: u8 = 0x04;
     pub const CTRL7: u8 = 0x08;
+    pub const CTRL9: u8 = 0x0a;
+    // Reused as scratch config registers by the CTRL9 WoM/tap commands.
+    pub const CAL1_L: u8 = 0x0b;
+    pub const CAL1_H: u8 = 0x0c;
+    pub const STATUSINT: u8 = 0x2d;
+    pub const STATUS1: u8 = 0x2f;
     pub const TEMP_L: u8 = 0x33;
     pub const AX_L: u8 = 0x35;
+    pub const FIFO_WM_TH: u8 = 0x13;
+    pub const FIFO_CTRL: u8 = 0x14;
+    pub const FIFO_SMPL_CNT: u8 = 0x15;
+    pub const FIFO_STATUS: u8 = 0x16;
+    pub const FIFO_DATA: u8 = 0x17;
+}
+
+mod ctrl9_commands {
+    /// Resets the FIFO and releases the latch taken by `REQ_FIFO`.
+    pub const RST_FIFO: u8 = 0x04;
+    /// Latches the current FIFO read pointer / sample count.
+    pub const REQ_FIFO: u8 = 0x05;
+    /// Applies the wake-on-motion threshold staged in `CAL1_L`/`CAL1_H`.
+    pub const WRITE_WOM_SETTING: u8 = 0x08;
+    /// Applies the tap-detection parameters staged in `CAL1_L`/`CAL1_H`.
+    pub const CONFIGURE_TAP: u8 = 0x0c;
+}
+
+mod status_bits {
+    /// `STATUS1` bit set by the wake-on-motion engine.
+    pub const WOM: u8 = 1 << 2;
+    /// `STATUS1` bit set by the tap engine (single or double tap).
+    pub const TAP: u8 = 1 << 1;
+    /// `STATUS1` bit set by the any-motion engine.
+    pub const ANY_MOTION: u8 = 1 << 0;
 }
 
 impl<I2C: I2c> Qmi8658a<I2C> {
     pub fn new(i2c: I2C, address: u8) -> Self {
-        Self { i2c, address }
+        Self {
+            i2c,
+            address,
+            accel_range: AccelRange::G2,
+            gyro_range: GyroRange::Dps2048,
+        }
     }
 
     pub fn read_chip_id(&mut self) -> Result<u8, I2C::Error> {
@@ -38,22 +245,40 @@ impl<I2C: I2c> Qmi8658a<I2C> {
         Ok(id[0])
     }
 
+    /// Initializes the sensor with the default [`Config`] (±2 g, ±2048 dps, 1 kHz).
     pub fn initialize(&mut self) -> Result<(), I2C::Error> {
+        self.initialize_with_config(Config::default())
+    }
+
+    /// Initializes the sensor, configuring the accelerometer and gyroscope
+    /// full-scale ranges and output data rates through `CTRL2`/`CTRL3`.
+    pub fn initialize_with_config(&mut self, config: Config) -> Result<(), I2C::Error> {
         let control1: u8 = 0b0110_0000;
         // CTRL7 gSN=0, aEN=1, gEN=1
         let control7: u8 = 0b0000_0011;
+        let control2 = config.accel_range.bits() | config.accel_odr.bits();
+        let control3 = config.gyro_range.bits() | config.gyro_odr.bits();
+
         self.i2c
             .write(self.address, &[registers::CTRL1, control1])?;
+        self.i2c
+            .write(self.address, &[registers::CTRL2, control2])?;
+        self.i2c
+            .write(self.address, &[registers::CTRL3, control3])?;
         self.i2c
             .write(self.address, &[registers::CTRL7, control7])?;
+
+        self.accel_range = config.accel_range;
+        self.gyro_range = config.gyro_range;
         Ok(())
     }
 
-    pub fn read_temperature(&mut self) -> Result<i16, I2C::Error> {
+    /// Reads the die temperature in degrees Celsius.
+    pub fn read_temperature(&mut self) -> Result<f32, I2C::Error> {
         let mut temperature = [0; 2];
         self.i2c
             .write_read(self.address, &[registers::TEMP_L], &mut temperature)?;
-        Ok(i16::from_le_bytes(temperature))
+        Ok(i16::from_le_bytes(temperature) as f32 / 256.0)
     }
 
     pub fn read_imu_data(&mut self) -> Result<ImuData, I2C::Error> {
@@ -70,4 +295,170 @@ impl<I2C: I2c> Qmi8658a<I2C> {
             gyro_z: i16::from_le_bytes(imu[10..12].try_into().unwrap()),
         })
     }
+
+    /// Reads acceleration in g, scaled using the configured [`AccelRange`].
+    pub fn read_accel_g(&mut self) -> Result<[f32; 3], I2C::Error> {
+        let imu = self.read_imu_data()?;
+        let sensitivity = self.accel_range.sensitivity();
+        Ok([
+            imu.accel_x as f32 / sensitivity,
+            imu.accel_y as f32 / sensitivity,
+            imu.accel_z as f32 / sensitivity,
+        ])
+    }
+
+    /// Reads angular rate in degrees per second, scaled using the configured [`GyroRange`].
+    pub fn read_gyro_dps(&mut self) -> Result<[f32; 3], I2C::Error> {
+        let imu = self.read_imu_data()?;
+        let sensitivity = self.gyro_range.sensitivity();
+        Ok([
+            imu.gyro_x as f32 / sensitivity,
+            imu.gyro_y as f32 / sensitivity,
+            imu.gyro_z as f32 / sensitivity,
+        ])
+    }
+
+    /// Configures the hardware FIFO's mode and watermark (in samples).
+    pub fn configure_fifo(&mut self, mode: FifoMode, watermark: u8) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[registers::FIFO_WM_TH, watermark])?;
+        self.i2c
+            .write(self.address, &[registers::FIFO_CTRL, mode.bits()])?;
+        Ok(())
+    }
+
+    /// Returns the number of samples currently buffered in the FIFO.
+    ///
+    /// This only latches and reads the count; it does not clear the FIFO,
+    /// so the caller can poll this until it reaches a watermark and then
+    /// drain the same samples with [`Self::read_fifo`].
+    pub fn fifo_count(&mut self) -> Result<u16, I2C::Error> {
+        self.write_ctrl9_command(ctrl9_commands::REQ_FIFO)?;
+        self.fifo_count_latched()
+    }
+
+    /// Reads `FIFO_SMPL_CNT`/`FIFO_STATUS`, assuming the caller already
+    /// latched the FIFO with `ctrl9_commands::REQ_FIFO`.
+    fn fifo_count_latched(&mut self) -> Result<u16, I2C::Error> {
+        let mut smpl_cnt_lo = [0];
+        self.i2c
+            .write_read(self.address, &[registers::FIFO_SMPL_CNT], &mut smpl_cnt_lo)?;
+        let mut status = [0];
+        self.i2c
+            .write_read(self.address, &[registers::FIFO_STATUS], &mut status)?;
+        // FIFO_STATUS[1:0] holds the sample count's upper two bits.
+        let smpl_cnt_hi = (status[0] & 0b11) as u16;
+        Ok((smpl_cnt_hi << 8) | smpl_cnt_lo[0] as u16)
+    }
+
+    /// Drains up to `out.len()` samples from the FIFO into `out`, returning
+    /// how many samples were actually read.
+    ///
+    /// If `out` is shorter than the number of samples available, only the
+    /// undrained remainder stays queued in the FIFO (the FIFO is only
+    /// reset once every available sample has been read) — a bounded `out`
+    /// is safe to call repeatedly across frames without losing samples.
+    /// Call [`Self::reset_fifo`] to explicitly discard whatever is queued.
+    pub fn read_fifo(&mut self, out: &mut [ImuData]) -> Result<usize, I2C::Error> {
+        self.write_ctrl9_command(ctrl9_commands::REQ_FIFO)?;
+        let available = self.fifo_count_latched()? as usize;
+        let to_read = available.min(out.len());
+
+        for sample in out.iter_mut().take(to_read) {
+            let mut raw = [0; 12];
+            self.i2c
+                .write_read(self.address, &[registers::FIFO_DATA], &mut raw)?;
+            *sample = ImuData {
+                accel_x: i16::from_le_bytes(raw[0..2].try_into().unwrap()),
+                accel_y: i16::from_le_bytes(raw[2..4].try_into().unwrap()),
+                accel_z: i16::from_le_bytes(raw[4..6].try_into().unwrap()),
+                gyro_x: i16::from_le_bytes(raw[6..8].try_into().unwrap()),
+                gyro_y: i16::from_le_bytes(raw[8..10].try_into().unwrap()),
+                gyro_z: i16::from_le_bytes(raw[10..12].try_into().unwrap()),
+            };
+        }
+
+        if to_read == available {
+            self.write_ctrl9_command(ctrl9_commands::RST_FIFO)?;
+        }
+
+        Ok(to_read)
+    }
+
+    /// Clears the FIFO, discarding any samples currently queued in it.
+    pub fn reset_fifo(&mut self) -> Result<(), I2C::Error> {
+        self.write_ctrl9_command(ctrl9_commands::RST_FIFO)
+    }
+
+    /// Issues a command through the `CTRL9` command interface, used by the
+    /// FIFO latch/reset and calibration/feature commands alike.
+    fn write_ctrl9_command(&mut self, command: u8) -> Result<(), I2C::Error> {
+        self.i2c.write(self.address, &[registers::CTRL9, command])
+    }
+
+    /// Enables the wake-on-motion engine, routing its interrupt to `pin`.
+    /// `threshold_mg` is the acceleration delta (in mg) that triggers a
+    /// wake event, from 0 to 255 mg.
+    pub fn enable_wake_on_motion(
+        &mut self,
+        threshold_mg: u8,
+        pin: InterruptPin,
+    ) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[registers::CAL1_L, threshold_mg])?;
+        // CAL1_H: bit7 enables WoM, bit6 selects the interrupt initial
+        // level, bits[5:0] set the blanking time.
+        self.i2c.write(self.address, &[registers::CAL1_H, 0b1000_0000])?;
+        self.write_ctrl9_command(ctrl9_commands::WRITE_WOM_SETTING)?;
+        self.enable_interrupt_pin(pin)
+    }
+
+    /// Enables the tap-detection engine, routing its interrupt to `pin`.
+    /// `threshold_mg` is the peak acceleration (in mg) a tap must exceed.
+    pub fn enable_tap_detection(
+        &mut self,
+        threshold_mg: u8,
+        pin: InterruptPin,
+    ) -> Result<(), I2C::Error> {
+        self.i2c
+            .write(self.address, &[registers::CAL1_L, threshold_mg])?;
+        // CAL1_H: bit0 enables the tap engine; the remaining bits keep the
+        // part's default priority axis and peak/window timing.
+        self.i2c.write(self.address, &[registers::CAL1_H, 0b0000_0001])?;
+        self.write_ctrl9_command(ctrl9_commands::CONFIGURE_TAP)?;
+        self.enable_interrupt_pin(pin)
+    }
+
+    fn enable_interrupt_pin(&mut self, pin: InterruptPin) -> Result<(), I2C::Error> {
+        let mut control1 = [0];
+        self.i2c
+            .write_read(self.address, &[registers::CTRL1], &mut control1)?;
+        self.i2c.write(
+            self.address,
+            &[registers::CTRL1, control1[0] | pin.enable_bit()],
+        )
+    }
+
+    /// Reads and decodes `STATUSINT`/`STATUS1`, reporting which motion
+    /// engine(s) fired since the last read. Pair with a GPIO input on the
+    /// IMU's `INTn` line to wake on this without polling I2C continuously.
+    pub fn read_interrupt_status(&mut self) -> Result<InterruptStatus, I2C::Error> {
+        let mut statusint = [0];
+        self.i2c
+            .write_read(self.address, &[registers::STATUSINT], &mut statusint)?;
+
+        if statusint[0] == 0 {
+            return Ok(InterruptStatus::default());
+        }
+
+        let mut status1 = [0];
+        self.i2c
+            .write_read(self.address, &[registers::STATUS1], &mut status1)?;
+
+        Ok(InterruptStatus {
+            wake_on_motion: status1[0] & status_bits::WOM != 0,
+            tap: status1[0] & status_bits::TAP != 0,
+            any_motion: status1[0] & status_bits::ANY_MOTION != 0,
+        })
+    }
 }
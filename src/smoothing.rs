@@ -0,0 +1,70 @@
+//! Exponential-moving-average smoothing for noisy `f32` samples, such as
+//! the per-frame accel/gyro readings (`[f32; 3]`, from
+//! [`crate::qmi8658a::Qmi8658a::read_accel_g`] and
+//! [`crate::qmi8658a::Qmi8658a::read_gyro_dps`]) and temperature readings.
+//!
+//! `k` trades latency for noise rejection: a small `k` (e.g. 0.05) rejects
+//! more noise but lags behind real changes more; a large `k` (close to 1.0)
+//! tracks the raw signal closely with little smoothing. `0.1` is a
+//! reasonable default for on-screen display values.
+
+/// Default smoothing factor used by [`Smoothed::new_default`].
+pub const DEFAULT_K: f32 = 0.1;
+
+/// A value that can be exponentially blended with its previous estimate.
+pub trait Smoothable: Copy {
+    fn blend(self, previous: Self, k: f32) -> Self;
+}
+
+impl Smoothable for f32 {
+    fn blend(self, previous: Self, k: f32) -> Self {
+        previous * (1.0 - k) + self * k
+    }
+}
+
+impl Smoothable for [f32; 3] {
+    fn blend(self, previous: Self, k: f32) -> Self {
+        [
+            previous[0] * (1.0 - k) + self[0] * k,
+            previous[1] * (1.0 - k) + self[1] * k,
+            previous[2] * (1.0 - k) + self[2] * k,
+        ]
+    }
+}
+
+/// Maintains a running exponential moving average of `T`, seeded on the
+/// first sample passed to [`Smoothed::update`].
+#[derive(Debug, Clone, Copy)]
+pub struct Smoothed<T> {
+    k: f32,
+    average: Option<T>,
+}
+
+impl<T: Smoothable> Smoothed<T> {
+    /// Creates a smoother with the given blend factor `k` (`avg = avg*(1-k)
+    /// + sample*k`).
+    pub fn new(k: f32) -> Self {
+        Self { k, average: None }
+    }
+
+    /// Creates a smoother using [`DEFAULT_K`].
+    pub fn new_default() -> Self {
+        Self::new(DEFAULT_K)
+    }
+
+    /// Folds `sample` into the running average and returns the updated
+    /// value. The first call seeds the average with `sample` directly.
+    pub fn update(&mut self, sample: T) -> T {
+        let average = match self.average {
+            Some(previous) => sample.blend(previous, self.k),
+            None => sample,
+        };
+        self.average = Some(average);
+        average
+    }
+
+    /// Returns the current average, if at least one sample has been seen.
+    pub fn value(&self) -> Option<T> {
+        self.average
+    }
+}
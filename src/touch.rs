@@ -0,0 +1,140 @@
+//! Touch event and gesture layer on top of a raw contact-point source (e.g.
+//! the `axs5106l` driver configured in `main`).
+//!
+//! This module does not talk to the touch controller itself; callers poll
+//! the driver each frame (or on the touch IRQ line) and feed the resulting
+//! point into [`TouchTracker::update`], which derives press/release/move
+//! events and simple tap/swipe gestures.
+
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::prelude::*;
+
+/// A touch contact point in display coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchPoint {
+    pub x: u16,
+    pub y: u16,
+}
+
+impl TouchPoint {
+    pub fn as_point(self) -> Point {
+        Point::new(self.x as i32, self.y as i32)
+    }
+
+    /// Returns whether this point falls inside `region`, for hit-testing
+    /// on-screen buttons drawn with `embedded_graphics`.
+    pub fn hits(self, region: Rectangle) -> bool {
+        region.contains(self.as_point())
+    }
+}
+
+/// A single-frame touch event, derived by comparing this frame's contact
+/// point against the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchEvent {
+    Pressed(TouchPoint),
+    Released(TouchPoint),
+    Moved(TouchPoint),
+}
+
+/// A dwell threshold, in milliseconds, at or below which a press/release
+/// pair with little movement is classified as a tap. A longer press with
+/// little movement produces no gesture.
+const TAP_MAX_DWELL_MS: u64 = 300;
+
+/// Minimum travel distance, in pixels along one axis, for a press/release
+/// pair to be classified as a swipe instead of a tap.
+const SWIPE_MIN_DISTANCE_PX: i32 = 20;
+
+/// A recognized higher-level gesture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    Tap(TouchPoint),
+    Swipe {
+        start: TouchPoint,
+        end: TouchPoint,
+        direction: SwipeDirection,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Tracks the current contact point across frames, turning raw polled
+/// points into [`TouchEvent`]s and [`Gesture`]s.
+#[derive(Debug, Default)]
+pub struct TouchTracker {
+    current: Option<TouchPoint>,
+    press_start: Option<(TouchPoint, u64)>,
+}
+
+impl TouchTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds this frame's contact point (`None` if the panel isn't touched)
+    /// and the current time in milliseconds, returning the event for this
+    /// frame, if any.
+    pub fn update(&mut self, point: Option<TouchPoint>, now_ms: u64) -> Option<TouchEvent> {
+        let event = match (self.current, point) {
+            (None, Some(p)) => {
+                self.press_start = Some((p, now_ms));
+                Some(TouchEvent::Pressed(p))
+            }
+            (Some(_), Some(p)) => Some(TouchEvent::Moved(p)),
+            (Some(p), None) => Some(TouchEvent::Released(p)),
+            (None, None) => None,
+        };
+
+        self.current = point;
+        event
+    }
+
+    /// Call with the same event returned from [`Self::update`]; returns a
+    /// [`Gesture`] once a press/release pair completes.
+    pub fn recognize(&mut self, event: TouchEvent, now_ms: u64) -> Option<Gesture> {
+        let TouchEvent::Released(end) = event else {
+            return None;
+        };
+        let (start, pressed_at_ms) = self.press_start.take()?;
+
+        let dx = end.x as i32 - start.x as i32;
+        let dy = end.y as i32 - start.y as i32;
+        let dwell_ms = now_ms.saturating_sub(pressed_at_ms);
+
+        if dx.abs() < SWIPE_MIN_DISTANCE_PX
+            && dy.abs() < SWIPE_MIN_DISTANCE_PX
+            && dwell_ms <= TAP_MAX_DWELL_MS
+        {
+            return Some(Gesture::Tap(end));
+        }
+
+        if dx.abs() < SWIPE_MIN_DISTANCE_PX && dy.abs() < SWIPE_MIN_DISTANCE_PX {
+            return None;
+        }
+
+        let direction = if dx.abs() > dy.abs() {
+            if dx > 0 {
+                SwipeDirection::Right
+            } else {
+                SwipeDirection::Left
+            }
+        } else if dy > 0 {
+            SwipeDirection::Down
+        } else {
+            SwipeDirection::Up
+        };
+
+        Some(Gesture::Swipe {
+            start,
+            end,
+            direction,
+        })
+    }
+}